@@ -0,0 +1,105 @@
+//! The pieces a concrete CPace instantiation must supply: a [`Group`] to carry out
+//! the Diffie-Hellman exchange in, a [`DigestHash`] to mix the transcript, and the
+//! domain separation strings and wire encodings tying the two together.
+
+use zeroize::Zeroize;
+
+/// A group CPace can run its Diffie-Hellman exchange in.
+///
+/// Implementors are responsible for deriving the session generator from the CPace
+/// transcript inputs in whatever way suits the group: a generic groups without a
+/// native hash-to-curve map (such as Ristretto255) hashes the inputs to uniform
+/// bytes and maps those to a point, while a group with a standard hash-to-curve
+/// encoding (such as P-256) can use it directly.
+pub trait Group {
+    /// A scalar, used for the ephemeral exponent `r`. Required to be `Zeroize` so
+    /// `CPace` can wipe it on drop.
+    type Scalar: Copy + Clone + Zeroize;
+
+    /// A group element.
+    type Point: Copy + Clone;
+
+    /// Size, in bytes, of a compressed point.
+    const POINT_BYTES: usize;
+
+    /// Derive the session's generator from the CPace transcript inputs.
+    fn generator(
+        dsi: &str,
+        password: &str,
+        session_id: &[u8],
+        id_a: &str,
+        id_b: &str,
+        ad: &[u8],
+    ) -> Self::Point;
+
+    /// Reduce 64 bytes of randomness to a scalar, for the ephemeral exponent `r`.
+    fn scalar_from_wide_bytes(bytes: &[u8; 64]) -> Self::Scalar;
+
+    /// Multiply a point by a scalar.
+    fn mul(point: Self::Point, scalar: &Self::Scalar) -> Self::Point;
+
+    /// Write a point's compressed encoding to `out`, which is exactly `POINT_BYTES`
+    /// long. Callers must reject the neutral element with [`Group::is_identity`]
+    /// before calling this: some encodings (e.g. P-256's SEC1 point-at-infinity)
+    /// don't have a `POINT_BYTES`-long representation for it.
+    fn compress(point: &Self::Point, out: &mut [u8]);
+
+    /// Decompress a point from its wire encoding.
+    fn decompress(bytes: &[u8]) -> Option<Self::Point>;
+
+    /// Returns `true` if `point` is the group's neutral element.
+    fn is_identity(point: &Self::Point) -> bool;
+}
+
+/// An incremental hash function, used to mix the CPace transcript into the shared keys.
+pub trait DigestHash: Clone {
+    /// Size, in bytes, of a full output.
+    const OUTPUT_BYTES: usize;
+
+    /// Size, in bytes, of the underlying compression function's block.
+    const BLOCK_BYTES: usize;
+
+    fn new() -> Self;
+    fn update(&mut self, data: impl AsRef<[u8]>);
+    fn finalize(self) -> [u8; 64];
+}
+
+/// A concrete pairing of a [`Group`] and a [`DigestHash`], along with the domain
+/// separation strings and wire encodings CPace needs to run over that pairing.
+///
+/// This is the extension point users pick between at the type level, e.g.
+/// `CPace<Ristretto255Sha512>` or `CPace<P256Sha256>`.
+pub trait CipherSuite {
+    type Group: Group;
+    type Hash: DigestHash;
+
+    /// Domain separation string mixed into the session's generator.
+    const DSI1: &'static str;
+
+    /// Domain separation string mixed into the shared keys.
+    const DSI2: &'static str;
+
+    /// Size, in bytes, of each of the two shared keys `step3`/`step2` produce.
+    const SHARED_KEY_BYTES: usize;
+
+    /// Wire encoding of a step 1 packet: `session_id || compressed point`.
+    type Step1Packet: AsRef<[u8]> + AsMut<[u8]> + Clone;
+
+    /// Wire encoding of a step 2 packet: a compressed point.
+    type Step2Packet: AsRef<[u8]> + AsMut<[u8]> + Clone;
+
+    /// A shared key, `SHARED_KEY_BYTES` long. Required to be `Zeroize` so
+    /// `SharedKeys` can wipe it on drop.
+    type SharedKey: AsRef<[u8]> + AsMut<[u8]> + Clone + Zeroize;
+
+    /// Size, in bytes, of a key-confirmation tag.
+    const CONFIRMATION_TAG_BYTES: usize;
+
+    /// A key-confirmation tag, `CONFIRMATION_TAG_BYTES` long.
+    type ConfirmationTag: AsRef<[u8]> + AsMut<[u8]> + Clone;
+
+    fn new_step1_packet() -> Self::Step1Packet;
+    fn new_step2_packet() -> Self::Step2Packet;
+    fn new_shared_key() -> Self::SharedKey;
+    fn new_confirmation_tag() -> Self::ConfirmationTag;
+}
@@ -0,0 +1,41 @@
+//! A minimal HMAC construction over a [`DigestHash`], used to compute key-confirmation
+//! tags. Built directly on `DigestHash` rather than pulling in a separate HMAC crate,
+//! so it works for every cipher suite's hash without extra trait plumbing.
+
+use crate::cipher_suite::DigestHash;
+
+/// Largest block size among the hash functions this crate supports (SHA-512's 128 bytes).
+const MAX_BLOCK_BYTES: usize = 128;
+
+/// Compute `HMAC(key, messages[0] || messages[1] || ...)` using hash function `H`.
+pub fn hmac<H: DigestHash>(key: &[u8], messages: &[&[u8]]) -> [u8; 64] {
+    let block_bytes = H::BLOCK_BYTES;
+    let mut key_block = [0u8; MAX_BLOCK_BYTES];
+    if key.len() > block_bytes {
+        let mut st = H::new();
+        st.update(key);
+        let hashed = st.finalize();
+        key_block[..H::OUTPUT_BYTES].copy_from_slice(&hashed[..H::OUTPUT_BYTES]);
+    } else {
+        key_block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut ipad = [0x36u8; MAX_BLOCK_BYTES];
+    let mut opad = [0x5cu8; MAX_BLOCK_BYTES];
+    for i in 0..block_bytes {
+        ipad[i] ^= key_block[i];
+        opad[i] ^= key_block[i];
+    }
+
+    let mut inner = H::new();
+    inner.update(&ipad[..block_bytes]);
+    for message in messages {
+        inner.update(message);
+    }
+    let inner_hash = inner.finalize();
+
+    let mut outer = H::new();
+    outer.update(&opad[..block_bytes]);
+    outer.update(&inner_hash[..H::OUTPUT_BYTES]);
+    outer.finalize()
+}
@@ -1,27 +1,51 @@
 #![no_std]
 #![forbid(unsafe_code)]
 
+mod cipher_suite;
+mod hmac;
+pub mod p256;
+pub mod ristretto;
+
 use core::fmt;
-use curve25519_dalek::{
-    ristretto::{CompressedRistretto, RistrettoPoint},
-    scalar::Scalar,
-};
+
 use getrandom::getrandom;
-use hmac_sha512::{Hash, BLOCKBYTES};
+use rand_core::{CryptoRng, RngCore};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-pub const SESSION_ID_BYTES: usize = 16;
-pub const STEP1_PACKET_BYTES: usize = 16 + 32;
-pub const STEP2_PACKET_BYTES: usize = 32;
-pub const SHARED_KEY_BYTES: usize = 32;
+pub use cipher_suite::{CipherSuite, DigestHash, Group};
+pub use p256::P256Sha256;
+pub use ristretto::Ristretto255Sha512;
+
+/// Domain separation label mixed into the key-confirmation MAC key, keeping it
+/// independent from the shared keys even though both are derived from the same
+/// transcript hash.
+const CONFIRMATION_KEY_LABEL: &str = "CPaceConfirmationKey";
+
+/// Label mixed into the initiator's (`A`, the `step1`/`step3` side) confirmation
+/// tag, so it can never be mistaken for the responder's.
+const INITIATOR_CONFIRMATION_LABEL: &str = "CPaceConfirmationA->B";
+
+/// Label mixed into the responder's (`B`, the `step2` side) confirmation tag.
+const RESPONDER_CONFIRMATION_LABEL: &str = "CPaceConfirmationB->A";
 
-const DSI1: &str = "CPaceRistretto255-1";
-const DSI2: &str = "CPaceRistretto255-1";
+/// Which side of the exchange a [`Confirmation`] is being derived for, so its tag
+/// is bound to a role and can't be reflected back as the other side's tag.
+#[derive(Copy, Clone)]
+enum Role {
+    Initiator,
+    Responder,
+}
+
+/// Size, in bytes, of a CPace session id. Fixed across every cipher suite.
+pub const SESSION_ID_BYTES: usize = 16;
 
 #[derive(Debug)]
 pub enum Error {
     Overflow(&'static str),
     Random(getrandom::Error),
     InvalidPublicKey,
+    Confirmation,
 }
 
 impl fmt::Display for Error {
@@ -36,100 +60,324 @@ impl From<getrandom::Error> for Error {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub struct SharedKeys {
-    pub k1: [u8; SHARED_KEY_BYTES],
-    pub k2: [u8; SHARED_KEY_BYTES],
+pub struct SharedKeys<CS: CipherSuite = Ristretto255Sha512> {
+    pub k1: CS::SharedKey,
+    pub k2: CS::SharedKey,
 }
 
-#[derive(Debug, Clone)]
-pub struct CPace {
+impl<CS: CipherSuite> Clone for SharedKeys<CS> {
+    fn clone(&self) -> Self {
+        SharedKeys {
+            k1: self.k1.clone(),
+            k2: self.k2.clone(),
+        }
+    }
+}
+
+impl<CS: CipherSuite> fmt::Debug for SharedKeys<CS> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SharedKeys")
+            .field("k1", &"[redacted]")
+            .field("k2", &"[redacted]")
+            .finish()
+    }
+}
+
+impl<CS: CipherSuite> Drop for SharedKeys<CS> {
+    fn drop(&mut self) {
+        self.k1.zeroize();
+        self.k2.zeroize();
+    }
+}
+
+impl<CS: CipherSuite> ZeroizeOnDrop for SharedKeys<CS> {}
+
+pub struct CPace<CS: CipherSuite = Ristretto255Sha512> {
     session_id: [u8; SESSION_ID_BYTES],
-    p: RistrettoPoint,
-    r: Scalar,
+    p: <CS::Group as Group>::Point,
+    r: <CS::Group as Group>::Scalar,
+}
+
+impl<CS: CipherSuite> fmt::Debug for CPace<CS> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CPace").finish_non_exhaustive()
+    }
+}
+
+impl<CS: CipherSuite> Drop for CPace<CS> {
+    fn drop(&mut self) {
+        self.r.zeroize();
+    }
 }
 
-pub struct Step1Out {
-    ctx: CPace,
-    step1_packet: [u8; STEP1_PACKET_BYTES],
+impl<CS: CipherSuite> ZeroizeOnDrop for CPace<CS> {}
+
+pub struct Step1Out<CS: CipherSuite = Ristretto255Sha512> {
+    ctx: CPace<CS>,
+    step1_packet: CS::Step1Packet,
 }
 
-impl Step1Out {
-    pub fn packet(&self) -> [u8; STEP1_PACKET_BYTES] {
-        self.step1_packet
+impl<CS: CipherSuite> Step1Out<CS> {
+    pub fn packet(&self) -> CS::Step1Packet {
+        self.step1_packet.clone()
     }
 
-    pub fn step3(&self, step2_packet: &[u8; STEP2_PACKET_BYTES]) -> Result<SharedKeys, Error> {
+    pub fn step3(&self, step2_packet: &CS::Step2Packet) -> Result<SharedKeys<CS>, Error> {
         self.ctx.step3(step2_packet)
     }
+
+    pub fn step3_with_confirmation(
+        &self,
+        step2_packet: &CS::Step2Packet,
+    ) -> Result<(SharedKeys<CS>, Confirmation<CS>), Error> {
+        self.ctx.step3_with_confirmation(step2_packet)
+    }
 }
 
-pub struct Step2Out {
-    shared_keys: SharedKeys,
-    step2_packet: [u8; STEP2_PACKET_BYTES],
+pub struct Step2Out<CS: CipherSuite = Ristretto255Sha512> {
+    shared_keys: SharedKeys<CS>,
+    step2_packet: CS::Step2Packet,
 }
 
-impl Step2Out {
-    pub fn shared_keys(&self) -> SharedKeys {
-        self.shared_keys
+impl<CS: CipherSuite> Step2Out<CS> {
+    pub fn shared_keys(&self) -> SharedKeys<CS> {
+        self.shared_keys.clone()
     }
 
-    pub fn packet(&self) -> [u8; STEP2_PACKET_BYTES] {
-        self.step2_packet
+    pub fn packet(&self) -> CS::Step2Packet {
+        self.step2_packet.clone()
     }
 }
 
-impl CPace {
-    fn new<T: AsRef<[u8]>>(
+/// A key-confirmation tag, proving to the other party that this end derived the
+/// same shared keys from the same transcript.
+///
+/// Obtained from [`CPace::step2_with_confirmation`] or
+/// [`Step1Out::step3_with_confirmation`]. The tag is bound to the sender's role
+/// (initiator vs. responder), so [`Confirmation::tag`] and [`Confirmation::verify`]
+/// operate on different values even within a single genuine exchange: a peer can't
+/// make its own tag verify by reflecting it back.
+pub struct Confirmation<CS: CipherSuite = Ristretto255Sha512> {
+    my_tag: CS::ConfirmationTag,
+    expected_peer_tag: CS::ConfirmationTag,
+}
+
+impl<CS: CipherSuite> Confirmation<CS> {
+    /// This party's own tag (`Tcb` for the responder, `Tca` for the initiator), to
+    /// send to the other party.
+    pub fn tag(&self) -> CS::ConfirmationTag {
+        self.my_tag.clone()
+    }
+
+    /// Check a tag received from the other party against the one expected from
+    /// their role, in constant time.
+    pub fn verify(&self, received: &CS::ConfirmationTag) -> Result<(), Error> {
+        if self
+            .expected_peer_tag
+            .as_ref()
+            .ct_eq(received.as_ref())
+            .into()
+        {
+            Ok(())
+        } else {
+            Err(Error::Confirmation)
+        }
+    }
+}
+
+impl<CS: CipherSuite> Clone for Confirmation<CS> {
+    fn clone(&self) -> Self {
+        Confirmation {
+            my_tag: self.my_tag.clone(),
+            expected_peer_tag: self.expected_peer_tag.clone(),
+        }
+    }
+}
+
+impl<CS: CipherSuite> fmt::Debug for Confirmation<CS> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Confirmation")
+            .field("tag", &self.my_tag.as_ref())
+            .field("expected_peer_tag", &self.expected_peer_tag.as_ref())
+            .finish()
+    }
+}
+
+impl<CS: CipherSuite> CPace<CS> {
+    fn from_r_seed<T: AsRef<[u8]>>(
         session_id: [u8; SESSION_ID_BYTES],
         password: &str,
         id_a: &str,
         id_b: &str,
         ad: Option<T>,
+        r_seed: &[u8; 64],
     ) -> Result<Self, Error> {
         if id_a.len() > 0xff || id_b.len() > 0xff {
             return Err(Error::Overflow(
                 "Identifiers must be at most 255 bytes long",
             ));
         }
-        let zpad = [0u8; BLOCKBYTES];
-        let pad_len = zpad.len().wrapping_sub(DSI1.len() + password.len()) & (zpad.len() - 1);
-        let mut st = Hash::new();
-        st.update(DSI1);
-        st.update(password);
-        st.update(&zpad[..pad_len]);
-        st.update(session_id);
-        st.update([id_a.len() as u8]);
-        st.update(id_a);
-        st.update([id_b.len() as u8]);
-        st.update(id_b);
-        st.update(ad.as_ref().map(|ad| ad.as_ref()).unwrap_or_default());
-        let h = st.finalize();
-        let mut p = RistrettoPoint::from_uniform_bytes(&h);
-        let mut r = [0u8; 64];
-        getrandom(&mut r)?;
-        let r = Scalar::from_bytes_mod_order_wide(&r);
-        p *= r;
+        let ad = ad.as_ref().map(|ad| ad.as_ref()).unwrap_or_default();
+        let g = CS::Group::generator(CS::DSI1, password, &session_id, id_a, id_b, ad);
+        let r = CS::Group::scalar_from_wide_bytes(r_seed);
+        let p = CS::Group::mul(g, &r);
+        // Negligible as landing on the neutral element is, `p` gets compressed onto
+        // the wire unconditionally in step1_packet_from_ctx/step2_out_from_ctx, and
+        // Group::compress's contract (an exact POINT_BYTES-long encoding) doesn't
+        // hold for the identity under every suite's encoding (e.g. P-256's SEC1
+        // point-at-infinity is a single byte). Reject it here instead of risking a
+        // panic downstream.
+        if CS::Group::is_identity(&p) {
+            return Err(Error::InvalidPublicKey);
+        }
         Ok(CPace { session_id, p, r })
     }
 
-    fn finalize(
+    fn new<T: AsRef<[u8]>>(
+        session_id: [u8; SESSION_ID_BYTES],
+        password: &str,
+        id_a: &str,
+        id_b: &str,
+        ad: Option<T>,
+    ) -> Result<Self, Error> {
+        let mut r_seed = [0u8; 64];
+        getrandom(&mut r_seed)?;
+        Self::from_r_seed(session_id, password, id_a, id_b, ad, &r_seed)
+    }
+
+    /// Like [`CPace::new`], but draw the ephemeral scalar `r` from a caller-supplied
+    /// RNG instead of the OS random number generator. Useful in `no_std`
+    /// environments without `getrandom` support, or to reproduce known-answer
+    /// vectors with a fixed seed.
+    fn new_with_rng<T: AsRef<[u8]>>(
+        rng: &mut (impl RngCore + CryptoRng),
+        session_id: [u8; SESSION_ID_BYTES],
+        password: &str,
+        id_a: &str,
+        id_b: &str,
+        ad: Option<T>,
+    ) -> Result<Self, Error> {
+        let mut r_seed = [0u8; 64];
+        rng.fill_bytes(&mut r_seed);
+        Self::from_r_seed(session_id, password, id_a, id_b, ad, &r_seed)
+    }
+
+    /// Compute the shared point `op * r` and reject it (and `op`, which was already
+    /// checked by the caller) if it is the neutral element, then compress the
+    /// transcript's three points for hashing.
+    fn transcript(
         &self,
-        op: RistrettoPoint,
-        ya: RistrettoPoint,
-        yb: RistrettoPoint,
-    ) -> Result<SharedKeys, Error> {
-        let p = op * self.r;
-        let mut st = Hash::new();
-        st.update(DSI2);
-        st.update(p.compress().as_bytes());
-        st.update(ya.compress().as_bytes());
-        st.update(yb.compress().as_bytes());
+        op: <CS::Group as Group>::Point,
+        ya: <CS::Group as Group>::Point,
+        yb: <CS::Group as Group>::Point,
+    ) -> Result<([u8; 64], [u8; 64], [u8; 64], usize), Error> {
+        let p = CS::Group::mul(op, &self.r);
+        if CS::Group::is_identity(&p) {
+            return Err(Error::InvalidPublicKey);
+        }
+        let point_bytes = <CS::Group as Group>::POINT_BYTES;
+        let mut p_buf = [0u8; 64];
+        let mut ya_buf = [0u8; 64];
+        let mut yb_buf = [0u8; 64];
+        CS::Group::compress(&p, &mut p_buf[..point_bytes]);
+        CS::Group::compress(&ya, &mut ya_buf[..point_bytes]);
+        CS::Group::compress(&yb, &mut yb_buf[..point_bytes]);
+        Ok((p_buf, ya_buf, yb_buf, point_bytes))
+    }
+
+    fn shared_keys_from_transcript(
+        &self,
+        p_buf: &[u8],
+        ya_buf: &[u8],
+        yb_buf: &[u8],
+    ) -> SharedKeys<CS> {
+        let mut st = CS::Hash::new();
+        st.update(CS::DSI2);
+        st.update(p_buf);
+        st.update(ya_buf);
+        st.update(yb_buf);
         let h = st.finalize();
-        let (mut k1, mut k2) = ([0u8; SHARED_KEY_BYTES], [0u8; SHARED_KEY_BYTES]);
-        k1.copy_from_slice(&h[..SHARED_KEY_BYTES]);
-        k2.copy_from_slice(&h[SHARED_KEY_BYTES..]);
-        Ok(SharedKeys { k1, k2 })
+
+        let n = CS::SHARED_KEY_BYTES;
+        let mut k1 = CS::new_shared_key();
+        let mut k2 = CS::new_shared_key();
+        k1.as_mut().copy_from_slice(&h[..n]);
+        k2.as_mut().copy_from_slice(&h[n..2 * n]);
+        SharedKeys { k1, k2 }
+    }
+
+    /// Derive both confirmation tags (`Tca` and `Tcb`) from the transcript, each
+    /// bound to its role by a distinct label, then return them arranged as
+    /// `(my_tag, expected_peer_tag)` for `role`.
+    fn confirmation_from_transcript(
+        &self,
+        role: Role,
+        p_buf: &[u8],
+        ya_buf: &[u8],
+        yb_buf: &[u8],
+    ) -> Confirmation<CS> {
+        let mut mst = CS::Hash::new();
+        mst.update(CS::DSI2);
+        mst.update(CONFIRMATION_KEY_LABEL);
+        mst.update(p_buf);
+        mst.update(ya_buf);
+        mst.update(yb_buf);
+        let mac_key = mst.finalize();
+        let mac_key = &mac_key[..CS::Hash::OUTPUT_BYTES];
+
+        let tag_from_label = |label: &str| {
+            let tag_bytes = hmac::hmac::<CS::Hash>(
+                mac_key,
+                &[CS::DSI2.as_bytes(), label.as_bytes(), ya_buf, yb_buf],
+            );
+            let mut tag = CS::new_confirmation_tag();
+            tag.as_mut()
+                .copy_from_slice(&tag_bytes[..CS::CONFIRMATION_TAG_BYTES]);
+            tag
+        };
+
+        let tca = tag_from_label(INITIATOR_CONFIRMATION_LABEL);
+        let tcb = tag_from_label(RESPONDER_CONFIRMATION_LABEL);
+        match role {
+            Role::Initiator => Confirmation {
+                my_tag: tca,
+                expected_peer_tag: tcb,
+            },
+            Role::Responder => Confirmation {
+                my_tag: tcb,
+                expected_peer_tag: tca,
+            },
+        }
+    }
+
+    fn finalize(
+        &self,
+        op: <CS::Group as Group>::Point,
+        ya: <CS::Group as Group>::Point,
+        yb: <CS::Group as Group>::Point,
+    ) -> Result<SharedKeys<CS>, Error> {
+        let (p_buf, ya_buf, yb_buf, point_bytes) = self.transcript(op, ya, yb)?;
+        Ok(self.shared_keys_from_transcript(
+            &p_buf[..point_bytes],
+            &ya_buf[..point_bytes],
+            &yb_buf[..point_bytes],
+        ))
+    }
+
+    fn finalize_with_confirmation(
+        &self,
+        role: Role,
+        op: <CS::Group as Group>::Point,
+        ya: <CS::Group as Group>::Point,
+        yb: <CS::Group as Group>::Point,
+    ) -> Result<(SharedKeys<CS>, Confirmation<CS>), Error> {
+        let (p_buf, ya_buf, yb_buf, point_bytes) = self.transcript(op, ya, yb)?;
+        let p_buf = &p_buf[..point_bytes];
+        let ya_buf = &ya_buf[..point_bytes];
+        let yb_buf = &yb_buf[..point_bytes];
+        let shared_keys = self.shared_keys_from_transcript(p_buf, ya_buf, yb_buf);
+        let confirmation = self.confirmation_from_transcript(role, p_buf, ya_buf, yb_buf);
+        Ok((shared_keys, confirmation))
     }
 
     pub fn step1<T: AsRef<[u8]>>(
@@ -137,32 +385,100 @@ impl CPace {
         id_a: &str,
         id_b: &str,
         ad: Option<T>,
-    ) -> Result<Step1Out, Error> {
+    ) -> Result<Step1Out<CS>, Error> {
         let mut session_id = [0u8; SESSION_ID_BYTES];
         getrandom(&mut session_id)?;
+        Self::step1_with_session_id(session_id, password, id_a, id_b, ad)
+    }
+
+    /// Like [`CPace::step1`], but use a `session_id` negotiated by some other
+    /// means instead of generating one locally.
+    pub fn step1_with_session_id<T: AsRef<[u8]>>(
+        session_id: [u8; SESSION_ID_BYTES],
+        password: &str,
+        id_a: &str,
+        id_b: &str,
+        ad: Option<T>,
+    ) -> Result<Step1Out<CS>, Error> {
         let ctx = CPace::new(session_id, password, id_a, id_b, ad)?;
-        let mut step1_packet = [0u8; STEP1_PACKET_BYTES];
-        step1_packet[..SESSION_ID_BYTES].copy_from_slice(&ctx.session_id);
-        step1_packet[SESSION_ID_BYTES..].copy_from_slice(ctx.p.compress().as_bytes());
-        Ok(Step1Out { ctx, step1_packet })
+        Ok(Self::step1_packet_from_ctx(ctx))
+    }
+
+    /// Like [`CPace::step1_with_session_id`], but draw the ephemeral scalar `r`
+    /// from a caller-supplied RNG instead of the OS random number generator.
+    pub fn step1_with_rng<T: AsRef<[u8]>>(
+        rng: &mut (impl RngCore + CryptoRng),
+        session_id: [u8; SESSION_ID_BYTES],
+        password: &str,
+        id_a: &str,
+        id_b: &str,
+        ad: Option<T>,
+    ) -> Result<Step1Out<CS>, Error> {
+        let ctx = CPace::new_with_rng(rng, session_id, password, id_a, id_b, ad)?;
+        Ok(Self::step1_packet_from_ctx(ctx))
+    }
+
+    fn step1_packet_from_ctx(ctx: CPace<CS>) -> Step1Out<CS> {
+        let mut step1_packet = CS::new_step1_packet();
+        {
+            let bytes = step1_packet.as_mut();
+            bytes[..SESSION_ID_BYTES].copy_from_slice(&ctx.session_id);
+            CS::Group::compress(&ctx.p, &mut bytes[SESSION_ID_BYTES..]);
+        }
+        Step1Out { ctx, step1_packet }
     }
 
     pub fn step2<T: AsRef<[u8]>>(
-        step1_packet: &[u8; STEP1_PACKET_BYTES],
+        step1_packet: &CS::Step1Packet,
         password: &str,
         id_a: &str,
         id_b: &str,
         ad: Option<T>,
-    ) -> Result<Step2Out, Error> {
-        let mut session_id = [0u8; SESSION_ID_BYTES];
-        session_id.copy_from_slice(&step1_packet[..SESSION_ID_BYTES]);
-        let ya = &step1_packet[SESSION_ID_BYTES..];
+    ) -> Result<Step2Out<CS>, Error> {
+        let session_id = Self::session_id_from_step1_packet(step1_packet);
         let ctx = CPace::new(session_id, password, id_a, id_b, ad)?;
-        let mut step2_packet = [0u8; STEP2_PACKET_BYTES];
-        step2_packet.copy_from_slice(ctx.p.compress().as_bytes());
-        let ya = CompressedRistretto::from_slice(ya)
-            .decompress()
-            .ok_or(Error::InvalidPublicKey)?;
+        Self::step2_out_from_ctx(ctx, step1_packet)
+    }
+
+    /// Like [`CPace::step2`], but draw the ephemeral scalar `r` from a
+    /// caller-supplied RNG instead of the OS random number generator.
+    pub fn step2_with_rng<T: AsRef<[u8]>>(
+        rng: &mut (impl RngCore + CryptoRng),
+        step1_packet: &CS::Step1Packet,
+        password: &str,
+        id_a: &str,
+        id_b: &str,
+        ad: Option<T>,
+    ) -> Result<Step2Out<CS>, Error> {
+        let session_id = Self::session_id_from_step1_packet(step1_packet);
+        let ctx = CPace::new_with_rng(rng, session_id, password, id_a, id_b, ad)?;
+        Self::step2_out_from_ctx(ctx, step1_packet)
+    }
+
+    fn session_id_from_step1_packet(step1_packet: &CS::Step1Packet) -> [u8; SESSION_ID_BYTES] {
+        let mut session_id = [0u8; SESSION_ID_BYTES];
+        session_id.copy_from_slice(&step1_packet.as_ref()[..SESSION_ID_BYTES]);
+        session_id
+    }
+
+    /// Decompress and validate `Ya` out of a step 1 packet, rejecting the
+    /// neutral element.
+    fn decode_ya(step1_packet: &CS::Step1Packet) -> Result<<CS::Group as Group>::Point, Error> {
+        let ya_bytes = &step1_packet.as_ref()[SESSION_ID_BYTES..];
+        let ya = CS::Group::decompress(ya_bytes).ok_or(Error::InvalidPublicKey)?;
+        if CS::Group::is_identity(&ya) {
+            return Err(Error::InvalidPublicKey);
+        }
+        Ok(ya)
+    }
+
+    fn step2_out_from_ctx(
+        ctx: CPace<CS>,
+        step1_packet: &CS::Step1Packet,
+    ) -> Result<Step2Out<CS>, Error> {
+        let ya = Self::decode_ya(step1_packet)?;
+        let mut step2_packet = CS::new_step2_packet();
+        CS::Group::compress(&ctx.p, step2_packet.as_mut());
         let shared_keys = ctx.finalize(ya, ya, ctx.p)?;
         Ok(Step2Out {
             shared_keys,
@@ -170,12 +486,59 @@ impl CPace {
         })
     }
 
-    pub fn step3(&self, step2_packet: &[u8; STEP2_PACKET_BYTES]) -> Result<SharedKeys, Error> {
-        let yb = CompressedRistretto::from_slice(step2_packet)
-            .decompress()
-            .ok_or(Error::InvalidPublicKey)?;
+    fn step2_out_from_ctx_with_confirmation(
+        ctx: CPace<CS>,
+        step1_packet: &CS::Step1Packet,
+    ) -> Result<(Step2Out<CS>, Confirmation<CS>), Error> {
+        let ya = Self::decode_ya(step1_packet)?;
+        let mut step2_packet = CS::new_step2_packet();
+        CS::Group::compress(&ctx.p, step2_packet.as_mut());
+        let (shared_keys, confirmation) =
+            ctx.finalize_with_confirmation(Role::Responder, ya, ya, ctx.p)?;
+        Ok((
+            Step2Out {
+                shared_keys,
+                step2_packet,
+            },
+            confirmation,
+        ))
+    }
+
+    /// Like [`CPace::step2`], but also derive this party's key-confirmation tag
+    /// (`Tcb`) for the caller to send to the other party alongside the packet.
+    pub fn step2_with_confirmation<T: AsRef<[u8]>>(
+        step1_packet: &CS::Step1Packet,
+        password: &str,
+        id_a: &str,
+        id_b: &str,
+        ad: Option<T>,
+    ) -> Result<(Step2Out<CS>, Confirmation<CS>), Error> {
+        let session_id = Self::session_id_from_step1_packet(step1_packet);
+        let ctx = CPace::new(session_id, password, id_a, id_b, ad)?;
+        Self::step2_out_from_ctx_with_confirmation(ctx, step1_packet)
+    }
+
+    pub fn step3(&self, step2_packet: &CS::Step2Packet) -> Result<SharedKeys<CS>, Error> {
+        let yb = CS::Group::decompress(step2_packet.as_ref()).ok_or(Error::InvalidPublicKey)?;
+        if CS::Group::is_identity(&yb) {
+            return Err(Error::InvalidPublicKey);
+        }
         self.finalize(yb, self.p, yb)
     }
+
+    /// Like [`CPace::step3`], but also derive this party's key-confirmation tag
+    /// (`Tca`), for the caller to verify against the peer's `Tcb` and to send back
+    /// in turn.
+    pub fn step3_with_confirmation(
+        &self,
+        step2_packet: &CS::Step2Packet,
+    ) -> Result<(SharedKeys<CS>, Confirmation<CS>), Error> {
+        let yb = CS::Group::decompress(step2_packet.as_ref()).ok_or(Error::InvalidPublicKey)?;
+        if CS::Group::is_identity(&yb) {
+            return Err(Error::InvalidPublicKey);
+        }
+        self.finalize_with_confirmation(Role::Initiator, yb, self.p, yb)
+    }
 }
 
 #[test]
@@ -189,3 +552,196 @@ fn test_cpace() {
     assert_eq!(shared_keys.k1, step2.shared_keys.k1);
     assert_eq!(shared_keys.k2, step2.shared_keys.k2);
 }
+
+#[test]
+fn test_cpace_confirmation() {
+    let client =
+        CPace::<Ristretto255Sha512>::step1("password", "client", "server", Some("ad")).unwrap();
+
+    let (step2, tcb) = CPace::<Ristretto255Sha512>::step2_with_confirmation(
+        &client.packet(),
+        "password",
+        "client",
+        "server",
+        Some("ad"),
+    )
+    .unwrap();
+
+    let (shared_keys, tca) = client.step3_with_confirmation(&step2.packet()).unwrap();
+    tca.verify(&tcb.tag()).unwrap();
+
+    // The initiator's and responder's tags must differ: a reflected tag must
+    // never verify as the other party's.
+    assert_ne!(tca.tag().as_ref(), tcb.tag().as_ref());
+
+    assert_eq!(shared_keys.k1, step2.shared_keys().k1);
+    assert_eq!(shared_keys.k2, step2.shared_keys().k2);
+}
+
+#[test]
+fn test_cpace_confirmation_wrong_password() {
+    let client =
+        CPace::<Ristretto255Sha512>::step1("password", "client", "server", Some("ad")).unwrap();
+
+    let (step2, tcb) = CPace::<Ristretto255Sha512>::step2_with_confirmation(
+        &client.packet(),
+        "wrong",
+        "client",
+        "server",
+        Some("ad"),
+    )
+    .unwrap();
+
+    let (_, tca) = client.step3_with_confirmation(&step2.packet()).unwrap();
+
+    assert!(matches!(tca.verify(&tcb.tag()), Err(Error::Confirmation)));
+}
+
+/// An RNG that always yields the same byte, used to feed `*_with_rng` a fixed
+/// scalar seed so a handshake can be reproduced byte-for-byte.
+#[cfg(test)]
+struct FixedRng(u8);
+
+#[cfg(test)]
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0u8; 4];
+        self.fill_bytes(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0u8; 8];
+        self.fill_bytes(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(self.0);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl CryptoRng for FixedRng {}
+
+#[test]
+fn test_cpace_with_rng_and_session_id_is_reproducible() {
+    // A fixed RNG and externally negotiated session id let a handshake be
+    // replayed deterministically, the seam through which the official CPace
+    // draft's known-answer vectors could be fed in. This test only checks
+    // that two local runs sharing that seed agree with each other: it does
+    // NOT assert against the draft's published k1/k2 bytes, since this
+    // environment has no way to pull in those vectors. Wiring up the actual
+    // known-answer test is still open.
+    let session_id = [0x42u8; SESSION_ID_BYTES];
+
+    let step1_a = CPace::<Ristretto255Sha512>::step1_with_rng(
+        &mut FixedRng(0x11),
+        session_id,
+        "password",
+        "client",
+        "server",
+        Some("ad"),
+    )
+    .unwrap();
+    let step1_b = CPace::<Ristretto255Sha512>::step1_with_rng(
+        &mut FixedRng(0x11),
+        session_id,
+        "password",
+        "client",
+        "server",
+        Some("ad"),
+    )
+    .unwrap();
+    assert_eq!(step1_a.packet(), step1_b.packet());
+
+    let step2_a = CPace::<Ristretto255Sha512>::step2_with_rng(
+        &mut FixedRng(0x22),
+        &step1_a.packet(),
+        "password",
+        "client",
+        "server",
+        Some("ad"),
+    )
+    .unwrap();
+    let step2_b = CPace::<Ristretto255Sha512>::step2_with_rng(
+        &mut FixedRng(0x22),
+        &step1_b.packet(),
+        "password",
+        "client",
+        "server",
+        Some("ad"),
+    )
+    .unwrap();
+    assert_eq!(step2_a.packet(), step2_b.packet());
+
+    let shared_keys_a = step1_a.step3(&step2_a.packet()).unwrap();
+    let shared_keys_b = step1_b.step3(&step2_b.packet()).unwrap();
+    assert_eq!(shared_keys_a.k1, shared_keys_b.k1);
+    assert_eq!(shared_keys_a.k2, shared_keys_b.k2);
+    assert_eq!(shared_keys_a.k1, step2_a.shared_keys.k1);
+}
+
+#[test]
+fn test_hmac_sha512_rfc4231_test_case_1() {
+    // RFC 4231 Test Case 1 for HMAC-SHA-512, used to validate the hand-rolled
+    // `hmac` primitive independently of the self-consistency checks the
+    // protocol-level confirmation tests give it.
+    let key = [0x0bu8; 20];
+    let data = b"Hi There";
+    let expected: [u8; 64] = [
+        0x87, 0xaa, 0x7c, 0xde, 0xa5, 0xef, 0x61, 0x9d, 0x4f, 0xf0, 0xb4, 0x24, 0x1a, 0x1d,
+        0x6c, 0xb0, 0x23, 0x79, 0xf4, 0xe2, 0xce, 0x4e, 0xc2, 0x78, 0x7a, 0xd0, 0xb3, 0x05,
+        0x45, 0xe1, 0x7c, 0xde, 0xda, 0xa8, 0x33, 0xb7, 0xd6, 0xb8, 0xa7, 0x02, 0x03, 0x8b,
+        0x27, 0x4e, 0xae, 0xa3, 0xf4, 0xe4, 0xbe, 0x9d, 0x91, 0x4e, 0xeb, 0x61, 0xf1, 0x70,
+        0x2e, 0x69, 0x6c, 0x20, 0x3a, 0x12, 0x68, 0x54,
+    ];
+
+    assert_eq!(hmac::hmac::<hmac_sha512::Hash>(&key, &[data]), expected);
+}
+
+#[test]
+fn test_cpace_rejects_identity_point() {
+    use curve25519_dalek::{ristretto::RistrettoPoint, traits::Identity};
+
+    let client =
+        CPace::<Ristretto255Sha512>::step1("password", "client", "server", Some("ad")).unwrap();
+
+    // A step 2 packet carrying the neutral element instead of a real Yb must be
+    // rejected rather than accepted as a (trivially predictable) shared secret.
+    let mut step2_packet = [0u8; 32];
+    step2_packet.copy_from_slice(RistrettoPoint::identity().compress().as_bytes());
+    assert!(matches!(
+        client.step3(&step2_packet),
+        Err(Error::InvalidPublicKey)
+    ));
+
+    // Likewise for a step 1 packet carrying the neutral element instead of a real Ya.
+    let mut step1_packet = [0u8; SESSION_ID_BYTES + 32];
+    step1_packet[SESSION_ID_BYTES..]
+        .copy_from_slice(RistrettoPoint::identity().compress().as_bytes());
+    assert!(matches!(
+        CPace::<Ristretto255Sha512>::step2(&step1_packet, "password", "client", "server", Some("ad")),
+        Err(Error::InvalidPublicKey)
+    ));
+}
+
+#[test]
+fn test_cpace_p256() {
+    let client =
+        CPace::<P256Sha256>::step1("password", "client", "server", Some("ad")).unwrap();
+
+    let step2 =
+        CPace::<P256Sha256>::step2(&client.packet(), "password", "client", "server", Some("ad"))
+            .unwrap();
+
+    let shared_keys = client.step3(&step2.packet()).unwrap();
+
+    assert_eq!(shared_keys.k1, step2.shared_keys.k1);
+    assert_eq!(shared_keys.k2, step2.shared_keys.k2);
+}
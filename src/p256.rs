@@ -0,0 +1,127 @@
+//! NIST P-256 + SHA-256, using the standard hash-to-curve encoding for the session
+//! generator instead of the zero-padded hash construction [`Ristretto255Sha512`]
+//! needs for a group without a native map-to-curve.
+//!
+//! [`Ristretto255Sha512`]: crate::Ristretto255Sha512
+
+use elliptic_curve::{
+    group::Group as _,
+    hash2curve::{ExpandMsgXmd, GroupDigest},
+    sec1::{FromEncodedPoint, ToEncodedPoint},
+};
+use p256::{AffinePoint, EncodedPoint, NistP256, ProjectivePoint, Scalar};
+use sha2::{Digest, Sha256};
+
+use crate::cipher_suite::{CipherSuite, DigestHash, Group};
+
+/// The domain separation tag fed into the hash-to-curve map, derived from `DSI1`.
+const P256_DST: &[u8] = b"CPaceP256-1_XMD:SHA-256_SSWU_RO_";
+
+/// P-256, paired with SHA-256 both for hash-to-curve and the transcript hash.
+pub struct P256Sha256;
+
+impl DigestHash for Sha256 {
+    const OUTPUT_BYTES: usize = 32;
+    const BLOCK_BYTES: usize = 64;
+
+    fn new() -> Self {
+        Digest::new()
+    }
+
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        Digest::update(self, data.as_ref())
+    }
+
+    fn finalize(self) -> [u8; 64] {
+        let digest = Digest::finalize(self);
+        let mut out = [0u8; 64];
+        out[..32].copy_from_slice(&digest);
+        out
+    }
+}
+
+impl Group for P256Sha256 {
+    type Scalar = Scalar;
+    type Point = ProjectivePoint;
+
+    const POINT_BYTES: usize = 33;
+
+    fn generator(
+        dsi: &str,
+        password: &str,
+        session_id: &[u8],
+        id_a: &str,
+        id_b: &str,
+        ad: &[u8],
+    ) -> Self::Point {
+        let id_a_len = [id_a.len() as u8];
+        let id_b_len = [id_b.len() as u8];
+        let msg = [
+            dsi.as_bytes(),
+            password.as_bytes(),
+            session_id,
+            &id_a_len,
+            id_a.as_bytes(),
+            &id_b_len,
+            id_b.as_bytes(),
+            ad,
+        ]
+        .concat();
+        NistP256::hash_from_bytes::<ExpandMsgXmd<Sha256>>(&[&msg], &[P256_DST])
+            .expect("hash-to-curve input is well-formed")
+    }
+
+    fn scalar_from_wide_bytes(bytes: &[u8; 64]) -> Self::Scalar {
+        NistP256::hash_to_scalar::<ExpandMsgXmd<Sha256>>(&[bytes], &[P256_DST])
+            .expect("64 bytes of randomness reduce to a scalar")
+    }
+
+    fn mul(point: Self::Point, scalar: &Self::Scalar) -> Self::Point {
+        point * scalar
+    }
+
+    fn compress(point: &Self::Point, out: &mut [u8]) {
+        out.copy_from_slice(point.to_affine().to_encoded_point(true).as_bytes());
+    }
+
+    fn decompress(bytes: &[u8]) -> Option<Self::Point> {
+        let encoded = EncodedPoint::from_bytes(bytes).ok()?;
+        Option::from(AffinePoint::from_encoded_point(&encoded)).map(ProjectivePoint::from)
+    }
+
+    fn is_identity(point: &Self::Point) -> bool {
+        bool::from(point.is_identity())
+    }
+}
+
+impl CipherSuite for P256Sha256 {
+    type Group = Self;
+    type Hash = Sha256;
+
+    const DSI1: &'static str = "CPaceP256-1";
+    const DSI2: &'static str = "CPaceP256-1";
+    // SHA-256 only has 32 bytes of output to split between the two shared keys.
+    const SHARED_KEY_BYTES: usize = 16;
+    const CONFIRMATION_TAG_BYTES: usize = 32;
+
+    type Step1Packet = [u8; crate::SESSION_ID_BYTES + 33];
+    type Step2Packet = [u8; 33];
+    type SharedKey = [u8; 16];
+    type ConfirmationTag = [u8; 32];
+
+    fn new_step1_packet() -> Self::Step1Packet {
+        [0u8; crate::SESSION_ID_BYTES + 33]
+    }
+
+    fn new_step2_packet() -> Self::Step2Packet {
+        [0u8; 33]
+    }
+
+    fn new_shared_key() -> Self::SharedKey {
+        [0u8; 16]
+    }
+
+    fn new_confirmation_tag() -> Self::ConfirmationTag {
+        [0u8; 32]
+    }
+}
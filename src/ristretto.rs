@@ -0,0 +1,113 @@
+//! Ristretto255 + SHA-512, the cipher suite originally hard-wired into this crate.
+
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::IsIdentity,
+};
+use hmac_sha512::{Hash, BLOCKBYTES};
+
+use crate::cipher_suite::{CipherSuite, DigestHash, Group};
+
+/// Ristretto255, paired with SHA-512 both for the session generator and the
+/// transcript hash.
+pub struct Ristretto255Sha512;
+
+impl DigestHash for Hash {
+    const OUTPUT_BYTES: usize = 64;
+    const BLOCK_BYTES: usize = BLOCKBYTES;
+
+    fn new() -> Self {
+        Hash::new()
+    }
+
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        Hash::update(self, data)
+    }
+
+    fn finalize(self) -> [u8; 64] {
+        Hash::finalize(self)
+    }
+}
+
+impl Group for Ristretto255Sha512 {
+    type Scalar = Scalar;
+    type Point = RistrettoPoint;
+
+    const POINT_BYTES: usize = 32;
+
+    fn generator(
+        dsi: &str,
+        password: &str,
+        session_id: &[u8],
+        id_a: &str,
+        id_b: &str,
+        ad: &[u8],
+    ) -> Self::Point {
+        let zpad = [0u8; BLOCKBYTES];
+        let pad_len = zpad.len().wrapping_sub(dsi.len() + password.len()) & (zpad.len() - 1);
+        let mut st = Hash::new();
+        st.update(dsi);
+        st.update(password);
+        st.update(&zpad[..pad_len]);
+        st.update(session_id);
+        st.update([id_a.len() as u8]);
+        st.update(id_a);
+        st.update([id_b.len() as u8]);
+        st.update(id_b);
+        st.update(ad);
+        let h = st.finalize();
+        RistrettoPoint::from_uniform_bytes(&h)
+    }
+
+    fn scalar_from_wide_bytes(bytes: &[u8; 64]) -> Self::Scalar {
+        Scalar::from_bytes_mod_order_wide(bytes)
+    }
+
+    fn mul(point: Self::Point, scalar: &Self::Scalar) -> Self::Point {
+        point * scalar
+    }
+
+    fn compress(point: &Self::Point, out: &mut [u8]) {
+        out.copy_from_slice(point.compress().as_bytes());
+    }
+
+    fn decompress(bytes: &[u8]) -> Option<Self::Point> {
+        CompressedRistretto::from_slice(bytes).decompress()
+    }
+
+    fn is_identity(point: &Self::Point) -> bool {
+        point.is_identity()
+    }
+}
+
+impl CipherSuite for Ristretto255Sha512 {
+    type Group = Self;
+    type Hash = Hash;
+
+    const DSI1: &'static str = "CPaceRistretto255-1";
+    const DSI2: &'static str = "CPaceRistretto255-1";
+    const SHARED_KEY_BYTES: usize = 32;
+    const CONFIRMATION_TAG_BYTES: usize = 64;
+
+    type Step1Packet = [u8; crate::SESSION_ID_BYTES + 32];
+    type Step2Packet = [u8; 32];
+    type SharedKey = [u8; 32];
+    type ConfirmationTag = [u8; 64];
+
+    fn new_step1_packet() -> Self::Step1Packet {
+        [0u8; crate::SESSION_ID_BYTES + 32]
+    }
+
+    fn new_step2_packet() -> Self::Step2Packet {
+        [0u8; 32]
+    }
+
+    fn new_shared_key() -> Self::SharedKey {
+        [0u8; 32]
+    }
+
+    fn new_confirmation_tag() -> Self::ConfirmationTag {
+        [0u8; 64]
+    }
+}